@@ -7,6 +7,8 @@ extern crate libc;
 pub mod mmap;
 pub mod sem;
 pub mod shm;
+pub mod sync;
+mod util;
 
 #[cfg(test)]
 mod test {
@@ -2,6 +2,7 @@
 
 use libc;
 use std::io;
+use std::ops::Range;
 use std::ptr;
 use std::os::unix::io::AsRawFd;
 
@@ -40,6 +41,118 @@ impl MemoryMap {
     pub fn len(&self) -> usize {
         self.len
     }
+
+    /// Flushes changes made to a file-backed mapping to the backing object,
+    /// waiting for the writeback to complete before returning.
+    ///
+    /// If `range` is provided, only that sub-range of the mapping is
+    /// flushed; otherwise the whole mapping is.
+    pub fn flush(&self, range: Option<Range<usize>>) -> io::Result<()> {
+        self.msync(range, libc::MS_SYNC)
+    }
+
+    /// Like `flush`, but schedules the writeback and returns without
+    /// waiting for it to complete.
+    pub fn flush_async(&self, range: Option<Range<usize>>) -> io::Result<()> {
+        self.msync(range, libc::MS_ASYNC)
+    }
+
+    fn msync(&self, range: Option<Range<usize>>, flags: libc::c_int) -> io::Result<()> {
+        let (ptr, len) = try!(self.range_ptr(range));
+
+        unsafe {
+            if libc::msync(ptr, len, flags) == 0 {
+                Ok(())
+            } else {
+                Err(io::Error::last_os_error())
+            }
+        }
+    }
+
+    /// Changes the read, write, and execute permissions of the pages in
+    /// this mapping.
+    pub fn protect(&mut self, read: bool, write: bool, exec: bool) -> io::Result<()> {
+        let prot = prot_flags(read, write, exec);
+
+        unsafe {
+            if libc::mprotect(self.base, self.len, prot) == 0 {
+                Ok(())
+            } else {
+                Err(io::Error::last_os_error())
+            }
+        }
+    }
+
+    /// Advises the kernel of the expected usage pattern of the pages in
+    /// this mapping, which it may use to tune readahead and caching
+    /// behavior.
+    ///
+    /// If `range` is provided, only that sub-range of the mapping is
+    /// advised; otherwise the whole mapping is.
+    pub fn advise(&self, advice: Advice, range: Option<Range<usize>>) -> io::Result<()> {
+        let (ptr, len) = try!(self.range_ptr(range));
+
+        unsafe {
+            if libc::madvise(ptr, len, advice.to_raw()) == 0 {
+                Ok(())
+            } else {
+                Err(io::Error::last_os_error())
+            }
+        }
+    }
+
+    fn range_ptr(&self, range: Option<Range<usize>>) -> io::Result<(*mut libc::c_void, usize)> {
+        let range = range.unwrap_or(0..self.len);
+        if range.start > range.end || range.end > self.len {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "range out of bounds"));
+        }
+
+        unsafe {
+            let ptr = (self.base as *mut u8).offset(range.start as isize) as *mut libc::c_void;
+            Ok((ptr, range.end - range.start))
+        }
+    }
+}
+
+fn prot_flags(read: bool, write: bool, exec: bool) -> libc::c_int {
+    let mut prot = libc::PROT_NONE;
+    if read {
+        prot |= libc::PROT_READ;
+    }
+    if write {
+        prot |= libc::PROT_WRITE;
+    }
+    if exec {
+        prot |= libc::PROT_EXEC;
+    }
+    prot
+}
+
+/// Advice given to the kernel about how a mapped memory region will be
+/// accessed.
+#[derive(Debug, Clone, Copy)]
+pub enum Advice {
+    /// The application expects to access the pages sequentially, from
+    /// lower addresses to higher ones.
+    Sequential,
+    /// The application expects to access the pages in a random order.
+    Random,
+    /// The application expects to access the pages in the near future.
+    WillNeed,
+    /// The application does not expect to access the pages in the near
+    /// future.
+    DontNeed,
+}
+
+impl Advice {
+    fn to_raw(&self) -> libc::c_int {
+        match *self {
+            Advice::Sequential => libc::MADV_SEQUENTIAL,
+            Advice::Random => libc::MADV_RANDOM,
+            Advice::WillNeed => libc::MADV_WILLNEED,
+            Advice::DontNeed => libc::MADV_DONTNEED,
+        }
+    }
 }
 
 /// A builder type for `MemoryMap`s.
@@ -121,16 +234,7 @@ impl MapOptions {
                  fd: libc::c_int,
                  offset: u64)
                  -> io::Result<MemoryMap> {
-        let mut prot = libc::PROT_NONE;
-        if self.read {
-            prot |= libc::PROT_READ;
-        }
-        if self.write {
-            prot |= libc::PROT_WRITE;
-        }
-        if self.exec {
-            prot |= libc::PROT_EXEC;
-        }
+        let prot = prot_flags(self.read, self.write, self.exec);
 
         if self.shared {
             flags |= libc::MAP_SHARED;
@@ -173,3 +277,69 @@ impl MapOptions {
         self.map_inner(len, libc::MAP_ANON, 0, 0)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use shm;
+
+    #[test]
+    fn flush() {
+        let name = "/posix-ipc-mmap-flush";
+        let shm = shm::OpenOptions::new().create_new(true).write(true).open(name).unwrap();
+        shm::unlink(name).unwrap();
+        shm.set_len(4096).unwrap();
+
+        let mut mmap = MapOptions::new().read(true).write(true).shared(true).map(4096, &shm, 0).unwrap();
+        unsafe {
+            *(mmap.as_mut_ptr() as *mut u8) = 1;
+        }
+
+        mmap.flush(None).unwrap();
+        mmap.flush(Some(0..4096)).unwrap();
+    }
+
+    #[test]
+    fn flush_async() {
+        let name = "/posix-ipc-mmap-flush-async";
+        let shm = shm::OpenOptions::new().create_new(true).write(true).open(name).unwrap();
+        shm::unlink(name).unwrap();
+        shm.set_len(4096).unwrap();
+
+        let mut mmap = MapOptions::new().read(true).write(true).shared(true).map(4096, &shm, 0).unwrap();
+        unsafe {
+            *(mmap.as_mut_ptr() as *mut u8) = 1;
+        }
+
+        mmap.flush_async(None).unwrap();
+        mmap.flush_async(Some(0..4096)).unwrap();
+    }
+
+    #[test]
+    fn protect() {
+        let mut mmap = MapOptions::new().read(true).write(true).map_anonymous(4096).unwrap();
+        unsafe {
+            *(mmap.as_mut_ptr() as *mut u8) = 1;
+        }
+
+        mmap.protect(true, false, false).unwrap();
+
+        let value = unsafe { *(mmap.as_ptr() as *const u8) };
+        assert_eq!(value, 1);
+    }
+
+    #[test]
+    fn advise() {
+        let mmap = MapOptions::new().read(true).write(true).map_anonymous(4096).unwrap();
+        mmap.advise(Advice::WillNeed, None).unwrap();
+        mmap.advise(Advice::DontNeed, Some(0..4096)).unwrap();
+    }
+
+    #[test]
+    fn range_out_of_bounds() {
+        let mmap = MapOptions::new().read(true).map_anonymous(4096).unwrap();
+        let err = mmap.flush(Some(0..8192)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+}
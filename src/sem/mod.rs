@@ -4,8 +4,16 @@ use libc;
 use std::error::Error;
 use std::fmt;
 use std::io;
+use std::time::{Duration, Instant};
+
+#[cfg(target_os = "macos")]
+use std::thread;
+
+#[cfg(not(target_os = "macos"))]
+use util::realtime_deadline;
 
 pub mod named;
+pub mod sysv;
 pub mod unnamed;
 
 struct RawSemaphore(*mut libc::sem_t);
@@ -35,10 +43,65 @@ impl RawSemaphore {
         }
     }
 
+    #[cfg(not(target_os = "macos"))]
+    fn wait_timeout(&self, dur: Duration) -> Result<(), TryWaitError> {
+        let ts = realtime_deadline(dur);
+        let r = unsafe { libc::sem_timedwait(self.0, &ts) };
+        if r < 0 {
+            match io::Error::last_os_error().raw_os_error().unwrap() {
+                libc::EDEADLK => panic!("semaphore wait would result in deadlock"),
+                libc::ETIMEDOUT => Err(TryWaitError(())),
+                e => {
+                    debug_assert_eq!(e, 0);
+                    Ok(())
+                }
+            }
+        } else {
+            Ok(())
+        }
+    }
+
+    // OSX has no sem_timedwait, so we fall back to polling with sem_trywait.
+    #[cfg(target_os = "macos")]
+    fn wait_timeout(&self, dur: Duration) -> Result<(), TryWaitError> {
+        let deadline = Instant::now() + dur;
+        loop {
+            match self.try_wait() {
+                Ok(()) => return Ok(()),
+                Err(e) => {
+                    if Instant::now() >= deadline {
+                        return Err(e);
+                    }
+                    thread::sleep(Duration::new(0, 200_000));
+                }
+            }
+        }
+    }
+
+    fn wait_deadline(&self, deadline: Instant) -> Result<(), TryWaitError> {
+        let now = Instant::now();
+        let dur = if deadline > now {
+            deadline - now
+        } else {
+            Duration::new(0, 0)
+        };
+        self.wait_timeout(dur)
+    }
+
     fn post(&self) {
         let r = unsafe { libc::sem_post(self.0) };
         debug_assert_eq!(r, 0);
     }
+
+    fn value(&self) -> io::Result<i32> {
+        let mut val = 0;
+        let r = unsafe { libc::sem_getvalue(self.0, &mut val) };
+        if r == 0 {
+            Ok(val)
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
 }
 
 /// An error returned when `try_wait` would have blocked.
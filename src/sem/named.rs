@@ -4,8 +4,9 @@ use libc;
 use std::io;
 use std::ffi::{OsStr, CString};
 use std::os::unix::ffi::OsStrExt;
+use std::time::{Duration, Instant};
 
-use sem::RawSemaphore;
+use sem::{RawSemaphore, TryWaitError};
 
 /// A POSIX named semaphore.
 pub struct Semaphore(RawSemaphore);
@@ -38,10 +39,43 @@ impl Semaphore {
         self.0.wait()
     }
 
+    /// Attempts to decrement the semaphore by 1, returning an error if the
+    /// semaphore's value is 0.
+    pub fn try_wait(&self) -> Result<(), TryWaitError> {
+        self.0.try_wait()
+    }
+
+    /// Decrements the semaphore by 1, blocking for up to `dur` if the
+    /// semaphore's value is 0.
+    ///
+    /// Returns an error if the timeout elapses before the semaphore can be
+    /// decremented.
+    pub fn wait_timeout(&self, dur: Duration) -> Result<(), TryWaitError> {
+        self.0.wait_timeout(dur)
+    }
+
+    /// Decrements the semaphore by 1, blocking until `deadline` if the
+    /// semaphore's value is 0.
+    ///
+    /// Returns an error if the deadline elapses before the semaphore can be
+    /// decremented.
+    pub fn wait_deadline(&self, deadline: Instant) -> Result<(), TryWaitError> {
+        self.0.wait_deadline(deadline)
+    }
+
     /// Increments the semaphore by 1.
     pub fn post(&self) {
         self.0.post()
     }
+
+    /// Returns the current value of the semaphore.
+    ///
+    /// If the semaphore currently has waiters, some platforms will report a
+    /// negative value whose magnitude is the number of threads waiting on
+    /// the semaphore; that value is returned as-is.
+    pub fn value(&self) -> io::Result<i32> {
+        self.0.value()
+    }
 }
 
 /// A builder for `Semaphore`s.
@@ -151,6 +185,44 @@ mod test {
         sem.wait();
     }
 
+    #[test]
+    fn try_wait() {
+        let name = "/posix-ipc-sem-try-wait";
+        let sem = OpenOptions::new().create_new(true).open(name).unwrap();
+        unlink(name).unwrap();
+        assert!(sem.try_wait().is_err());
+        sem.post();
+        sem.try_wait().unwrap();
+    }
+
+    #[test]
+    fn wait_timeout() {
+        let name = "/posix-ipc-sem-wait-timeout";
+        let sem = OpenOptions::new().create_new(true).open(name).unwrap();
+        unlink(name).unwrap();
+
+        let dur = Duration::from_millis(100);
+        let start = Instant::now();
+        assert!(sem.wait_timeout(dur).is_err());
+        assert!(start.elapsed() >= dur);
+
+        sem.post();
+        sem.wait_timeout(dur).unwrap();
+    }
+
+    #[test]
+    fn value() {
+        let name = "/posix-ipc-sem-value";
+        let sem = OpenOptions::new().create_new(true).open(name).unwrap();
+        unlink(name).unwrap();
+
+        assert_eq!(sem.value().unwrap(), 0);
+        sem.post();
+        assert_eq!(sem.value().unwrap(), 1);
+        sem.wait();
+        assert_eq!(sem.value().unwrap(), 0);
+    }
+
     #[test]
     fn open_missing() {
         let name = "/posix-ipc-sem-open-missing";
@@ -0,0 +1,26 @@
+//! Generation of System V IPC keys.
+
+use libc;
+use std::ffi::CString;
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+
+/// Generates a System V IPC key derived from an existing path and a project
+/// identifier.
+///
+/// Any two processes that agree on the path and identifier will derive the
+/// same key without needing a side channel to communicate it, letting them
+/// rendezvous on the same `SemaphoreSet`.
+pub fn ftok<P: AsRef<Path>>(path: P, id: u8) -> io::Result<libc::key_t> {
+    let path = try!(CString::new(path.as_ref().as_os_str().as_bytes()));
+
+    unsafe {
+        let key = libc::ftok(path.as_ptr(), id as libc::c_int);
+        if key == -1 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(key)
+        }
+    }
+}
@@ -0,0 +1,246 @@
+//! System V semaphore sets.
+//!
+//! Unlike POSIX named and unnamed semaphores, a System V semaphore set
+//! groups several semaphores together and lets them be adjusted
+//! atomically as a single batch via `operate`, which a single POSIX
+//! semaphore cannot express.
+//!
+//! Linux and FreeBSD are supported; the `semun` union passed to `semctl`
+//! is not part of the stable ABI on every platform, so other targets may
+//! need adjustment.
+
+use libc;
+use std::io;
+
+pub mod key;
+
+/// A single operation applied to a semaphore within a `SemaphoreSet`.
+///
+/// A batch of `Op`s is applied atomically by `SemaphoreSet::operate`.
+#[derive(Debug, Clone, Copy)]
+pub struct Op {
+    num: u16,
+    op: i16,
+    flags: libc::c_short,
+}
+
+impl Op {
+    /// Creates a new operation adjusting semaphore number `num` by `delta`.
+    ///
+    /// A positive `delta` increments the semaphore. A negative `delta`
+    /// decrements it, blocking until the value would not drop below 0. A
+    /// `delta` of `0` blocks until the semaphore's value reaches 0.
+    pub fn new(num: u16, delta: i16) -> Op {
+        Op {
+            num: num,
+            op: delta,
+            flags: 0,
+        }
+    }
+
+    /// Sets the option to fail the whole `operate` batch with `EAGAIN`
+    /// rather than block if this operation cannot proceed immediately.
+    pub fn nowait(&mut self, nowait: bool) -> &mut Op {
+        self.set_flag(libc::IPC_NOWAIT as libc::c_short, nowait);
+        self
+    }
+
+    /// Sets the option to have the kernel automatically reverse this
+    /// operation's adjustment if the calling process exits, preventing
+    /// leaked counts if it crashes while holding them.
+    pub fn undo(&mut self, undo: bool) -> &mut Op {
+        self.set_flag(libc::SEM_UNDO as libc::c_short, undo);
+        self
+    }
+
+    fn set_flag(&mut self, flag: libc::c_short, set: bool) {
+        if set {
+            self.flags |= flag;
+        } else {
+            self.flags &= !flag;
+        }
+    }
+
+    fn to_sembuf(&self) -> libc::sembuf {
+        libc::sembuf {
+            sem_num: self.num,
+            sem_op: self.op,
+            sem_flg: self.flags,
+        }
+    }
+}
+
+#[repr(C)]
+union semun {
+    val: libc::c_int,
+    array: *mut u16,
+}
+
+/// A System V semaphore set.
+pub struct SemaphoreSet {
+    id: libc::c_int,
+}
+
+impl SemaphoreSet {
+    /// Creates a new semaphore set of `nsems` semaphores, failing if one
+    /// already exists for `key`.
+    pub fn create(key: libc::key_t, nsems: usize, mode: u32) -> io::Result<SemaphoreSet> {
+        let flags = libc::IPC_CREAT | libc::IPC_EXCL | mode as libc::c_int;
+        SemaphoreSet::semget(key, nsems, flags)
+    }
+
+    /// Opens an existing semaphore set.
+    pub fn open(key: libc::key_t) -> io::Result<SemaphoreSet> {
+        SemaphoreSet::semget(key, 0, 0)
+    }
+
+    fn semget(key: libc::key_t, nsems: usize, flags: libc::c_int) -> io::Result<SemaphoreSet> {
+        unsafe {
+            let id = libc::semget(key, nsems as libc::c_int, flags);
+            if id < 0 {
+                Err(io::Error::last_os_error())
+            } else {
+                Ok(SemaphoreSet { id: id })
+            }
+        }
+    }
+
+    /// Removes the semaphore set.
+    ///
+    /// Any process blocked in `operate` on this set will be woken up with
+    /// an error.
+    pub fn remove(&self) -> io::Result<()> {
+        unsafe {
+            if libc::semctl(self.id, 0, libc::IPC_RMID) == 0 {
+                Ok(())
+            } else {
+                Err(io::Error::last_os_error())
+            }
+        }
+    }
+
+    /// Atomically applies a batch of operations to the semaphores in this
+    /// set.
+    ///
+    /// The kernel applies every operation in the batch, or none of them;
+    /// it blocks until the whole batch can proceed unless an operation has
+    /// `IPC_NOWAIT` set, in which case it fails with `EAGAIN` instead.
+    pub fn operate(&self, ops: &[Op]) -> io::Result<()> {
+        let mut sembufs = ops.iter().map(Op::to_sembuf).collect::<Vec<_>>();
+
+        unsafe {
+            if libc::semop(self.id, sembufs.as_mut_ptr(), sembufs.len()) == 0 {
+                Ok(())
+            } else {
+                Err(io::Error::last_os_error())
+            }
+        }
+    }
+
+    /// Returns the current value of semaphore number `num` in the set.
+    pub fn get_value(&self, num: u16) -> io::Result<libc::c_int> {
+        unsafe {
+            let r = libc::semctl(self.id, num as libc::c_int, libc::GETVAL);
+            if r < 0 {
+                Err(io::Error::last_os_error())
+            } else {
+                Ok(r)
+            }
+        }
+    }
+
+    /// Sets the value of semaphore number `num` in the set.
+    pub fn set_value(&self, num: u16, value: libc::c_int) -> io::Result<()> {
+        unsafe {
+            let arg = semun { val: value };
+            if libc::semctl(self.id, num as libc::c_int, libc::SETVAL, arg) == 0 {
+                Ok(())
+            } else {
+                Err(io::Error::last_os_error())
+            }
+        }
+    }
+
+    /// Sets the values of every semaphore in the set at once.
+    ///
+    /// `values` must have one entry per semaphore in the set.
+    pub fn set_all(&self, values: &[u16]) -> io::Result<()> {
+        let mut values = values.to_vec();
+
+        unsafe {
+            let arg = semun { array: values.as_mut_ptr() };
+            if libc::semctl(self.id, 0, libc::SETALL, arg) == 0 {
+                Ok(())
+            } else {
+                Err(io::Error::last_os_error())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn create(nsems: usize) -> SemaphoreSet {
+        SemaphoreSet::create(libc::IPC_PRIVATE, nsems, 0o666).unwrap()
+    }
+
+    #[test]
+    fn create_get_value() {
+        let set = create(1);
+        assert_eq!(set.get_value(0).unwrap(), 0);
+        set.remove().unwrap();
+    }
+
+    #[test]
+    fn set_value() {
+        let set = create(1);
+        set.set_value(0, 2).unwrap();
+        assert_eq!(set.get_value(0).unwrap(), 2);
+        set.remove().unwrap();
+    }
+
+    #[test]
+    fn set_all() {
+        let set = create(3);
+        set.set_all(&[1, 2, 3]).unwrap();
+        assert_eq!(set.get_value(0).unwrap(), 1);
+        assert_eq!(set.get_value(1).unwrap(), 2);
+        assert_eq!(set.get_value(2).unwrap(), 3);
+        set.remove().unwrap();
+    }
+
+    #[test]
+    fn operate() {
+        let set = create(1);
+        set.set_value(0, 1).unwrap();
+
+        set.operate(&[Op::new(0, -1)]).unwrap();
+        assert_eq!(set.get_value(0).unwrap(), 0);
+
+        set.operate(&[Op::new(0, 1)]).unwrap();
+        assert_eq!(set.get_value(0).unwrap(), 1);
+
+        set.remove().unwrap();
+    }
+
+    #[test]
+    fn operate_nowait() {
+        let set = create(1);
+        set.set_value(0, 0).unwrap();
+
+        let mut op = Op::new(0, -1);
+        op.nowait(true);
+        assert!(set.operate(&[op]).is_err());
+
+        set.remove().unwrap();
+    }
+
+    #[test]
+    fn remove() {
+        let set = create(1);
+        set.remove().unwrap();
+        assert!(set.get_value(0).is_err());
+    }
+}
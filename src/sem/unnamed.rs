@@ -2,6 +2,7 @@
 
 use libc;
 use std::io;
+use std::time::{Duration, Instant};
 
 use sem::{RawSemaphore, TryWaitError};
 
@@ -47,10 +48,37 @@ impl Semaphore {
         self.0.try_wait()
     }
 
+    /// Decrements the semaphore by 1, blocking for up to `dur` if the
+    /// semaphore's value is 0.
+    ///
+    /// Returns an error if the timeout elapses before the semaphore can be
+    /// decremented.
+    pub fn wait_timeout(&self, dur: Duration) -> Result<(), TryWaitError> {
+        self.0.wait_timeout(dur)
+    }
+
+    /// Decrements the semaphore by 1, blocking until `deadline` if the
+    /// semaphore's value is 0.
+    ///
+    /// Returns an error if the deadline elapses before the semaphore can be
+    /// decremented.
+    pub fn wait_deadline(&self, deadline: Instant) -> Result<(), TryWaitError> {
+        self.0.wait_deadline(deadline)
+    }
+
     /// Increments the semaphore by 1.
     pub fn post(&self) {
         self.0.post()
     }
+
+    /// Returns the current value of the semaphore.
+    ///
+    /// If the semaphore currently has waiters, some platforms will report a
+    /// negative value whose magnitude is the number of threads waiting on
+    /// the semaphore; that value is returned as-is.
+    pub fn value(&self) -> io::Result<i32> {
+        self.0.value()
+    }
 }
 
 /// An unowned, unnamed, IPC semaphore.
@@ -74,8 +102,35 @@ impl SemaphoreRef {
         self.0.wait()
     }
 
+    /// Decrements the semaphore by 1, blocking for up to `dur` if the
+    /// semaphore's value is 0.
+    ///
+    /// Returns an error if the timeout elapses before the semaphore can be
+    /// decremented.
+    pub fn wait_timeout(&self, dur: Duration) -> Result<(), TryWaitError> {
+        self.0.wait_timeout(dur)
+    }
+
+    /// Decrements the semaphore by 1, blocking until `deadline` if the
+    /// semaphore's value is 0.
+    ///
+    /// Returns an error if the deadline elapses before the semaphore can be
+    /// decremented.
+    pub fn wait_deadline(&self, deadline: Instant) -> Result<(), TryWaitError> {
+        self.0.wait_deadline(deadline)
+    }
+
     /// Increments the semaphore by 1.
     pub fn post(&self) {
         self.0.post()
     }
+
+    /// Returns the current value of the semaphore.
+    ///
+    /// If the semaphore currently has waiters, some platforms will report a
+    /// negative value whose magnitude is the number of threads waiting on
+    /// the semaphore; that value is returned as-is.
+    pub fn value(&self) -> io::Result<i32> {
+        self.0.value()
+    }
 }
@@ -0,0 +1,189 @@
+//! Process-shared condition variables.
+
+use libc;
+use std::io;
+use std::mem;
+use std::time::Duration;
+
+use sync::{RawCondvar, WaitTimeoutResult};
+use sync::mutex::MutexGuard;
+
+/// A condition variable that can be shared between processes.
+///
+/// The condition variable is initialized directly on top of caller-owned
+/// memory (typically an `mmap::MemoryMap` backed by a `shm::SharedMemory`),
+/// so that a `CondvarRef` in another process can attach to it.
+///
+/// OSX does not support process-shared condition variables.
+pub struct Condvar(RawCondvar);
+
+impl Drop for Condvar {
+    fn drop(&mut self) {
+        unsafe {
+            libc::pthread_cond_destroy((self.0).0);
+        }
+    }
+}
+
+impl Condvar {
+    /// Initializes a process-shared condition variable.
+    ///
+    /// # Safety
+    ///
+    /// The caller is responsible for ensuring that the memory pointed to by
+    /// `cond` remains valid for the lifetime of the returned object and of
+    /// any `CondvarRef`s attached to it.
+    pub unsafe fn new(cond: *mut libc::pthread_cond_t) -> io::Result<Condvar> {
+        init(cond).map(|()| Condvar(RawCondvar(cond)))
+    }
+
+    /// Blocks the current thread until this condition variable is notified,
+    /// releasing `guard`'s mutex while blocked and reacquiring it before
+    /// returning.
+    pub fn wait<'a>(&self, guard: MutexGuard<'a>) -> MutexGuard<'a> {
+        self.0.wait(guard.raw());
+        guard
+    }
+
+    /// Like `wait`, but bounds the wait to `dur`.
+    pub fn wait_timeout<'a>(&self,
+                             guard: MutexGuard<'a>,
+                             dur: Duration)
+                             -> (MutexGuard<'a>, WaitTimeoutResult) {
+        let result = self.0.wait_timeout(guard.raw(), dur);
+        (guard, result)
+    }
+
+    /// Wakes up one thread blocked on this condition variable.
+    pub fn notify_one(&self) {
+        self.0.notify_one()
+    }
+
+    /// Wakes up all threads blocked on this condition variable.
+    pub fn notify_all(&self) {
+        self.0.notify_all()
+    }
+}
+
+unsafe fn init(cond: *mut libc::pthread_cond_t) -> io::Result<()> {
+    let mut attr = mem::zeroed();
+
+    let r = libc::pthread_condattr_init(&mut attr);
+    if r != 0 {
+        return Err(io::Error::from_raw_os_error(r));
+    }
+
+    let result = init_pshared(&mut attr, cond);
+    libc::pthread_condattr_destroy(&mut attr);
+    result
+}
+
+unsafe fn init_pshared(attr: &mut libc::pthread_condattr_t,
+                        cond: *mut libc::pthread_cond_t)
+                        -> io::Result<()> {
+    let r = libc::pthread_condattr_setpshared(attr, libc::PTHREAD_PROCESS_SHARED);
+    if r != 0 {
+        return Err(io::Error::from_raw_os_error(r));
+    }
+
+    let r = libc::pthread_cond_init(cond, attr);
+    if r == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::from_raw_os_error(r))
+    }
+}
+
+/// An unowned, process-shared condition variable.
+///
+/// A `CondvarRef` attaches to a condition variable that has already been
+/// initialized by a `Condvar` in (typically) another process.
+pub struct CondvarRef(RawCondvar);
+
+impl CondvarRef {
+    /// Creates a new `CondvarRef` referencing a previously initialized
+    /// process-shared condition variable.
+    ///
+    /// # Safety
+    ///
+    /// The caller is responsible for ensuring that `cond` references a
+    /// valid, initialized process-shared condition variable for the
+    /// lifetime of the returned object.
+    pub unsafe fn new(cond: *mut libc::pthread_cond_t) -> CondvarRef {
+        CondvarRef(RawCondvar(cond))
+    }
+
+    /// Blocks the current thread until this condition variable is notified,
+    /// releasing `guard`'s mutex while blocked and reacquiring it before
+    /// returning.
+    pub fn wait<'a>(&self, guard: MutexGuard<'a>) -> MutexGuard<'a> {
+        self.0.wait(guard.raw());
+        guard
+    }
+
+    /// Like `wait`, but bounds the wait to `dur`.
+    pub fn wait_timeout<'a>(&self,
+                             guard: MutexGuard<'a>,
+                             dur: Duration)
+                             -> (MutexGuard<'a>, WaitTimeoutResult) {
+        let result = self.0.wait_timeout(guard.raw(), dur);
+        (guard, result)
+    }
+
+    /// Wakes up one thread blocked on this condition variable.
+    pub fn notify_one(&self) {
+        self.0.notify_one()
+    }
+
+    /// Wakes up all threads blocked on this condition variable.
+    pub fn notify_all(&self) {
+        self.0.notify_all()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::thread;
+
+    use sync::mutex::{Mutex, MutexRef};
+
+    struct Shared {
+        mutex: libc::pthread_mutex_t,
+        cond: libc::pthread_cond_t,
+        ready: bool,
+    }
+
+    #[test]
+    #[cfg_attr(target_os = "macos", ignore)]
+    fn two_threads() {
+        let mut shared = Box::new(Shared {
+            mutex: unsafe { mem::zeroed() },
+            cond: unsafe { mem::zeroed() },
+            ready: false,
+        });
+
+        let mutex = unsafe { Mutex::new(&mut shared.mutex) }.unwrap();
+        let cond = unsafe { Condvar::new(&mut shared.cond) }.unwrap();
+        let ptr = &mut *shared as *mut Shared as usize;
+
+        let handle = thread::spawn(move || {
+            let shared = unsafe { &mut *(ptr as *mut Shared) };
+            let mutex_ref = unsafe { MutexRef::new(&mut shared.mutex) };
+            let cond_ref = unsafe { CondvarRef::new(&mut shared.cond) };
+
+            let guard = mutex_ref.lock().unwrap();
+            shared.ready = true;
+            drop(guard);
+            cond_ref.notify_one();
+        });
+
+        let mut guard = mutex.lock().unwrap();
+        while !shared.ready {
+            guard = cond.wait(guard);
+        }
+        drop(guard);
+
+        handle.join().unwrap();
+    }
+}
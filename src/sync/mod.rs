@@ -0,0 +1,114 @@
+//! Process-shared synchronization primitives.
+//!
+//! Unlike `std::sync`, these types are initialized directly on top of
+//! caller-owned memory (typically an `mmap::MemoryMap` backed by a
+//! `shm::SharedMemory`), following the same pattern as `sem::unnamed`, so
+//! that two unrelated processes holding separate mappings of the same
+//! memory can synchronize with each other. The backing memory must outlive
+//! every handle attached to it.
+//!
+//! OSX does not robustly support process-shared mutexes or condition
+//! variables.
+
+use libc;
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::time::Duration;
+
+use util::realtime_deadline;
+
+pub mod condvar;
+pub mod mutex;
+
+struct RawMutex(*mut libc::pthread_mutex_t);
+
+impl RawMutex {
+    // Returns `Ok(true)` if the mutex was acquired but its previous owner
+    // died while holding it (only possible for robust mutexes).
+    fn lock(&self) -> io::Result<bool> {
+        match unsafe { libc::pthread_mutex_lock(self.0) } {
+            0 => Ok(false),
+            libc::EOWNERDEAD => Ok(true),
+            e => Err(io::Error::from_raw_os_error(e)),
+        }
+    }
+
+    fn try_lock(&self) -> Result<io::Result<bool>, TryLockError> {
+        match unsafe { libc::pthread_mutex_trylock(self.0) } {
+            0 => Ok(Ok(false)),
+            libc::EOWNERDEAD => Ok(Ok(true)),
+            libc::EBUSY => Err(TryLockError(())),
+            e => Ok(Err(io::Error::from_raw_os_error(e))),
+        }
+    }
+
+    fn unlock(&self) {
+        let r = unsafe { libc::pthread_mutex_unlock(self.0) };
+        debug_assert_eq!(r, 0);
+    }
+
+    fn mark_consistent(&self) {
+        let r = unsafe { libc::pthread_mutex_consistent(self.0) };
+        debug_assert_eq!(r, 0);
+    }
+}
+
+/// An error returned when `try_lock` would have blocked.
+#[derive(Debug)]
+pub struct TryLockError(());
+
+impl fmt::Display for TryLockError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str(self.description())
+    }
+}
+
+impl Error for TryLockError {
+    fn description(&self) -> &str {
+        "try_lock call failed because the operation would block"
+    }
+}
+
+struct RawCondvar(*mut libc::pthread_cond_t);
+
+impl RawCondvar {
+    fn wait(&self, mutex: &RawMutex) {
+        let r = unsafe { libc::pthread_cond_wait(self.0, mutex.0) };
+        debug_assert_eq!(r, 0);
+    }
+
+    fn wait_timeout(&self, mutex: &RawMutex, dur: Duration) -> WaitTimeoutResult {
+        let ts = realtime_deadline(dur);
+        let r = unsafe { libc::pthread_cond_timedwait(self.0, mutex.0, &ts) };
+        match r {
+            0 => WaitTimeoutResult(false),
+            libc::ETIMEDOUT => WaitTimeoutResult(true),
+            e => {
+                debug_assert_eq!(e, 0);
+                WaitTimeoutResult(false)
+            }
+        }
+    }
+
+    fn notify_one(&self) {
+        let r = unsafe { libc::pthread_cond_signal(self.0) };
+        debug_assert_eq!(r, 0);
+    }
+
+    fn notify_all(&self) {
+        let r = unsafe { libc::pthread_cond_broadcast(self.0) };
+        debug_assert_eq!(r, 0);
+    }
+}
+
+/// Indicates whether a `Condvar` wait timed out.
+#[derive(Debug)]
+pub struct WaitTimeoutResult(bool);
+
+impl WaitTimeoutResult {
+    /// Returns whether the wait timed out.
+    pub fn timed_out(&self) -> bool {
+        self.0
+    }
+}
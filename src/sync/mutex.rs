@@ -0,0 +1,324 @@
+//! Process-shared mutexes.
+
+use libc;
+use std::cell::Cell;
+use std::io;
+use std::mem;
+
+use sync::{RawMutex, TryLockError};
+
+/// A mutex that can be shared between processes.
+///
+/// The mutex is initialized directly on top of caller-owned memory
+/// (typically an `mmap::MemoryMap` backed by a `shm::SharedMemory`), so
+/// that a `MutexRef` in another process can attach to the same lock.
+///
+/// OSX does not support process-shared mutexes.
+pub struct Mutex(RawMutex);
+
+impl Drop for Mutex {
+    fn drop(&mut self) {
+        unsafe {
+            libc::pthread_mutex_destroy((self.0).0);
+        }
+    }
+}
+
+impl Mutex {
+    /// Initializes a process-shared mutex.
+    ///
+    /// This is equivalent to `MutexOptions::new().init(mutex)`.
+    ///
+    /// # Safety
+    ///
+    /// The caller is responsible for ensuring that the memory pointed to by
+    /// `mutex` remains valid for the lifetime of the returned object and of
+    /// any `MutexRef`s attached to it.
+    pub unsafe fn new(mutex: *mut libc::pthread_mutex_t) -> io::Result<Mutex> {
+        MutexOptions::new().init(mutex)
+    }
+
+    /// Acquires the mutex, blocking until it is available.
+    ///
+    /// If the mutex is robust and its previous owner died while holding the
+    /// lock, this still succeeds, but the returned guard's
+    /// `recovery_needed` will return `true`.
+    pub fn lock(&self) -> io::Result<MutexGuard> {
+        self.0.lock().map(|recovery_needed| MutexGuard::new(&self.0, recovery_needed))
+    }
+
+    /// Attempts to acquire the mutex without blocking.
+    pub fn try_lock(&self) -> Result<io::Result<MutexGuard>, TryLockError> {
+        self.0.try_lock().map(|r| r.map(|recovery_needed| MutexGuard::new(&self.0, recovery_needed)))
+    }
+}
+
+/// A builder for `Mutex`es.
+pub struct MutexOptions {
+    robust: bool,
+}
+
+impl MutexOptions {
+    /// Creates a new `MutexOptions` with default settings.
+    pub fn new() -> MutexOptions {
+        MutexOptions { robust: false }
+    }
+
+    /// Sets the option for creating a robust mutex.
+    ///
+    /// A robust mutex can still be acquired after its previous owner died
+    /// while holding it; the lock is granted, but the guard reports that
+    /// recovery is needed so the caller can repair the state the mutex
+    /// protects. If the caller never acknowledges the recovery, the mutex
+    /// is left permanently inconsistent, and any future lock attempt fails
+    /// with `ENOTRECOVERABLE`.
+    pub fn robust(&mut self, robust: bool) -> &mut MutexOptions {
+        self.robust = robust;
+        self
+    }
+
+    /// Initializes a process-shared mutex.
+    ///
+    /// # Safety
+    ///
+    /// The caller is responsible for ensuring that the memory pointed to by
+    /// `mutex` remains valid for the lifetime of the returned object and of
+    /// any `MutexRef`s attached to it.
+    pub unsafe fn init(&self, mutex: *mut libc::pthread_mutex_t) -> io::Result<Mutex> {
+        init(self.robust, mutex).map(|()| Mutex(RawMutex(mutex)))
+    }
+}
+
+unsafe fn init(robust: bool, mutex: *mut libc::pthread_mutex_t) -> io::Result<()> {
+    let mut attr = mem::zeroed();
+
+    let r = libc::pthread_mutexattr_init(&mut attr);
+    if r != 0 {
+        return Err(io::Error::from_raw_os_error(r));
+    }
+
+    let result = init_pshared(&mut attr, robust, mutex);
+    libc::pthread_mutexattr_destroy(&mut attr);
+    result
+}
+
+unsafe fn init_pshared(attr: &mut libc::pthread_mutexattr_t,
+                        robust: bool,
+                        mutex: *mut libc::pthread_mutex_t)
+                        -> io::Result<()> {
+    let r = libc::pthread_mutexattr_setpshared(attr, libc::PTHREAD_PROCESS_SHARED);
+    if r != 0 {
+        return Err(io::Error::from_raw_os_error(r));
+    }
+
+    if robust {
+        let r = libc::pthread_mutexattr_setrobust(attr, libc::PTHREAD_MUTEX_ROBUST);
+        if r != 0 {
+            return Err(io::Error::from_raw_os_error(r));
+        }
+    }
+
+    let r = libc::pthread_mutex_init(mutex, attr);
+    if r == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::from_raw_os_error(r))
+    }
+}
+
+/// An unowned, process-shared mutex.
+///
+/// A `MutexRef` attaches to a mutex that has already been initialized by a
+/// `Mutex` in (typically) another process.
+pub struct MutexRef(RawMutex);
+
+impl MutexRef {
+    /// Creates a new `MutexRef` referencing a previously initialized
+    /// process-shared mutex.
+    ///
+    /// # Safety
+    ///
+    /// The caller is responsible for ensuring that `mutex` references a
+    /// valid, initialized process-shared mutex for the lifetime of the
+    /// returned object.
+    pub unsafe fn new(mutex: *mut libc::pthread_mutex_t) -> MutexRef {
+        MutexRef(RawMutex(mutex))
+    }
+
+    /// Acquires the mutex, blocking until it is available.
+    ///
+    /// If the mutex is robust and its previous owner died while holding the
+    /// lock, this still succeeds, but the returned guard's
+    /// `recovery_needed` will return `true`.
+    pub fn lock(&self) -> io::Result<MutexGuard> {
+        self.0.lock().map(|recovery_needed| MutexGuard::new(&self.0, recovery_needed))
+    }
+
+    /// Attempts to acquire the mutex without blocking.
+    pub fn try_lock(&self) -> Result<io::Result<MutexGuard>, TryLockError> {
+        self.0.try_lock().map(|r| r.map(|recovery_needed| MutexGuard::new(&self.0, recovery_needed)))
+    }
+}
+
+/// An RAII guard which releases a mutex's lock when dropped.
+pub struct MutexGuard<'a> {
+    mutex: &'a RawMutex,
+    recovery_needed: bool,
+    recovered: Cell<bool>,
+}
+
+impl<'a> Drop for MutexGuard<'a> {
+    fn drop(&mut self) {
+        if self.recovery_needed && self.recovered.get() {
+            self.mutex.mark_consistent();
+        }
+        self.mutex.unlock();
+    }
+}
+
+impl<'a> MutexGuard<'a> {
+    fn new(mutex: &'a RawMutex, recovery_needed: bool) -> MutexGuard<'a> {
+        MutexGuard {
+            mutex: mutex,
+            recovery_needed: recovery_needed,
+            recovered: Cell::new(false),
+        }
+    }
+
+    /// Returns `true` if this robust mutex's previous owner died while
+    /// holding the lock, leaving the state it protects in a possibly
+    /// inconsistent state.
+    ///
+    /// This can only be `true` for mutexes created with
+    /// `MutexOptions::robust`.
+    pub fn recovery_needed(&self) -> bool {
+        self.recovery_needed
+    }
+
+    /// Acknowledges that the state protected by the mutex has been
+    /// repaired.
+    ///
+    /// This should be called once `recovery_needed` is handled and before
+    /// this guard is dropped. Otherwise, the mutex is left marked
+    /// inconsistent, and any future lock attempt will fail with
+    /// `ENOTRECOVERABLE`.
+    pub fn mark_recovered(&self) {
+        self.recovered.set(true);
+    }
+
+    pub(super) fn raw(&self) -> &RawMutex {
+        self.mutex
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::mem;
+    use std::thread;
+    use std::time::Duration;
+
+    use mmap;
+
+    #[test]
+    #[cfg_attr(target_os = "macos", ignore)]
+    fn lock_unlock() {
+        let mut raw = unsafe { mem::zeroed() };
+        let mutex = unsafe { Mutex::new(&mut raw) }.unwrap();
+
+        let guard = mutex.lock().unwrap();
+        assert!(!guard.recovery_needed());
+        drop(guard);
+
+        mutex.try_lock().unwrap().unwrap();
+    }
+
+    #[test]
+    #[cfg_attr(target_os = "macos", ignore)]
+    fn two_threads() {
+        let mut raw = Box::new(unsafe { mem::zeroed() });
+        let mutex = unsafe { Mutex::new(&mut *raw) }.unwrap();
+        let ptr = &mut *raw as *mut libc::pthread_mutex_t as usize;
+
+        let handle = thread::spawn(move || {
+            let mutex_ref = unsafe { MutexRef::new(ptr as *mut libc::pthread_mutex_t) };
+            let guard = mutex_ref.lock().unwrap();
+            thread::sleep(Duration::from_millis(50));
+            drop(guard);
+        });
+
+        // give the spawned thread a chance to grab the lock first
+        thread::sleep(Duration::from_millis(10));
+        let guard = mutex.lock().unwrap();
+        assert!(!guard.recovery_needed());
+        drop(guard);
+
+        handle.join().unwrap();
+    }
+
+    // These exercise the robust-recovery path with a real dead owner, so
+    // the mutex has to live in memory actually shared across processes,
+    // not just a fork's copy-on-write snapshot of the parent's heap.
+    fn robust_mutex() -> (mmap::MemoryMap, Mutex) {
+        let mut mmap = mmap::MapOptions::new()
+            .read(true)
+            .write(true)
+            .shared(true)
+            .map_anonymous(mem::size_of::<libc::pthread_mutex_t>())
+            .unwrap();
+        let ptr = mmap.as_mut_ptr() as *mut libc::pthread_mutex_t;
+        let mutex = unsafe { MutexOptions::new().robust(true).init(ptr) }.unwrap();
+        (mmap, mutex)
+    }
+
+    // Forks a child that locks `mutex` and dies while still holding it,
+    // then waits for it to exit.
+    fn die_while_locked(mutex: &Mutex) {
+        let pid = unsafe { libc::fork() };
+        assert!(pid >= 0);
+
+        if pid == 0 {
+            let guard = mutex.lock().unwrap();
+            mem::forget(guard);
+            unsafe { libc::_exit(0) };
+        }
+
+        let mut status = 0;
+        assert_eq!(unsafe { libc::waitpid(pid, &mut status, 0) }, pid);
+    }
+
+    #[test]
+    #[cfg_attr(target_os = "macos", ignore)]
+    fn robust_recovery() {
+        let (_mmap, mutex) = robust_mutex();
+        die_while_locked(&mutex);
+
+        let guard = mutex.lock().unwrap();
+        assert!(guard.recovery_needed());
+        guard.mark_recovered();
+        drop(guard);
+
+        // recovery was acknowledged, so the mutex is usable again
+        let guard = mutex.lock().unwrap();
+        assert!(!guard.recovery_needed());
+    }
+
+    #[test]
+    #[cfg_attr(target_os = "macos", ignore)]
+    fn robust_unrecovered_is_unusable() {
+        let (_mmap, mutex) = robust_mutex();
+        die_while_locked(&mutex);
+
+        {
+            let guard = mutex.lock().unwrap();
+            assert!(guard.recovery_needed());
+            // deliberately not calling mark_recovered()
+        }
+
+        match mutex.lock() {
+            Err(ref e) if e.raw_os_error() == Some(libc::ENOTRECOVERABLE) => {}
+            Ok(_) => panic!("lock succeeded on an unrecovered inconsistent mutex"),
+            Err(e) => panic!("expected ENOTRECOVERABLE, got {}", e),
+        };
+    }
+}
@@ -0,0 +1,28 @@
+//! Internal helpers shared across IPC primitives.
+
+use libc;
+use std::time::Duration;
+
+/// Computes an absolute `CLOCK_REALTIME` deadline `dur` in the future, for
+/// use with `sem_timedwait`/`pthread_*_timedwait`.
+pub fn realtime_deadline(dur: Duration) -> libc::timespec {
+    let mut ts = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    unsafe {
+        libc::clock_gettime(libc::CLOCK_REALTIME, &mut ts);
+    }
+
+    let mut sec = ts.tv_sec + dur.as_secs() as libc::time_t;
+    let mut nsec = ts.tv_nsec + dur.subsec_nanos() as libc::c_long;
+    if nsec >= 1_000_000_000 {
+        nsec -= 1_000_000_000;
+        sec += 1;
+    }
+
+    libc::timespec {
+        tv_sec: sec,
+        tv_nsec: nsec,
+    }
+}